@@ -1,11 +1,18 @@
+mod export;
+mod mandelbrot;
+mod render;
+
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    cursor::Show,
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, MouseButton, MouseEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{
     backend::{Backend, CrosstermBackend},
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Paragraph},
@@ -13,15 +20,72 @@ use ratatui::{
     Frame, Terminal,
 };
 use std::{
+    collections::VecDeque,
     io::{self, Result},
-    time::Duration,
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::{Duration, Instant},
 };
 
+use export::ExportRequest;
+use mandelbrot::{palette_color, Palette};
+use render::{ChunkRenderer, ViewRect};
+
+const CANVAS_WIDTH: usize = 80;
+const CANVAS_HEIGHT: usize = 40;
+
+/// Rolling frame-time/throughput stats shown in the Info panel.
+struct FrameStats {
+    history: VecDeque<Duration>,
+    recomputed_pixels: usize,
+}
+
+impl FrameStats {
+    const WINDOW: usize = 30;
+
+    fn new() -> Self {
+        FrameStats {
+            history: VecDeque::with_capacity(Self::WINDOW),
+            recomputed_pixels: 0,
+        }
+    }
+
+    fn record(&mut self, frame_time: Duration, recomputed_pixels: usize) {
+        if self.history.len() == Self::WINDOW {
+            self.history.pop_front();
+        }
+        self.history.push_back(frame_time);
+        self.recomputed_pixels = recomputed_pixels;
+    }
+
+    fn avg_ms(&self) -> f64 {
+        if self.history.is_empty() {
+            return 0.0;
+        }
+        let total: Duration = self.history.iter().sum();
+        total.as_secs_f64() * 1000.0 / self.history.len() as f64
+    }
+
+    fn fps(&self) -> f64 {
+        let ms = self.avg_ms();
+        if ms <= 0.0 {
+            0.0
+        } else {
+            1000.0 / ms
+        }
+    }
+}
+
 struct App {
     zoom: f64,
     center_x: f64,
     center_y: f64,
     max_iterations: u32,
+    renderer: ChunkRenderer,
+    palette: Palette,
+    frame_stats: FrameStats,
+    last_export: Option<String>,
+    export_rx: Option<Receiver<String>>,
 }
 
 impl Default for App {
@@ -31,6 +95,11 @@ impl Default for App {
             center_x: -0.5,
             center_y: 0.0,
             max_iterations: 100,
+            renderer: ChunkRenderer::new(CANVAS_WIDTH, CANVAS_HEIGHT),
+            palette: Palette::Rainbow,
+            frame_stats: FrameStats::new(),
+            last_export: None,
+            export_rx: None,
         }
     }
 }
@@ -61,45 +130,154 @@ impl App {
     }
 
     fn increase_iterations(&mut self) {
-        self.max_iterations = (self.max_iterations + 20).min(500);
+        // The parallel chunked renderer keeps input responsive well past the
+        // old single-threaded cap, so let iterations climb much higher.
+        self.max_iterations = (self.max_iterations + 20).min(10_000);
     }
 
     fn decrease_iterations(&mut self) {
         self.max_iterations = (self.max_iterations.saturating_sub(20)).max(20);
     }
-}
 
-fn mandelbrot_iterations(c: (f64, f64), max_iter: u32) -> u32 {
-    let mut z = (0.0, 0.0);
-    for i in 0..max_iter {
-        if z.0 * z.0 + z.1 * z.1 > 4.0 {
-            return i;
+    fn cycle_palette(&mut self) {
+        self.palette = self.palette.next();
+    }
+
+    /// Kicks off a PPM export on a background thread so the multi-million
+    /// sample render doesn't freeze input handling; does nothing if one is
+    /// already running. The result is picked up later by `poll_export`.
+    fn export_view(&mut self) {
+        if self.export_rx.is_some() {
+            return;
         }
-        z = (z.0 * z.0 - z.1 * z.1 + c.0, 2.0 * z.0 * z.1 + c.1);
+
+        let request = ExportRequest {
+            view: self.view_rect(),
+            center_x: self.center_x,
+            center_y: self.center_y,
+            zoom: self.zoom,
+            max_iterations: self.max_iterations,
+            palette: self.palette,
+        };
+
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let message = match export::export_ppm(request) {
+                Ok(filename) => format!("saved {filename}"),
+                Err(err) => format!("export failed: {err}"),
+            };
+            let _ = tx.send(message);
+        });
+
+        self.export_rx = Some(rx);
+        self.last_export = Some("exporting...".to_string());
+    }
+
+    /// Picks up the background export's result, if it has finished.
+    fn poll_export(&mut self) {
+        if let Some(rx) = &self.export_rx {
+            if let Ok(message) = rx.try_recv() {
+                self.last_export = Some(message);
+                self.export_rx = None;
+            }
+        }
+    }
+
+    /// The complex-plane rectangle currently visible on the canvas, derived
+    /// from zoom and center the same way for both rendering and input mapping.
+    fn view_rect(&self) -> ViewRect {
+        let aspect_ratio = CANVAS_WIDTH as f64 / CANVAS_HEIGHT as f64;
+        let range = 2.0 / self.zoom;
+        ViewRect {
+            x_min: self.center_x - range * aspect_ratio,
+            x_max: self.center_x + range * aspect_ratio,
+            y_min: self.center_y - range,
+            y_max: self.center_y + range,
+        }
+    }
+
+    /// Maps a fractional position within the canvas (0.0..1.0 on each axis)
+    /// to the complex-plane point it currently shows.
+    fn point_at(&self, frac: (f64, f64)) -> (f64, f64) {
+        let view = self.view_rect();
+        // `frac.1` runs top to bottom, while the canvas draws `y_min` at the bottom.
+        (
+            view.x_min + frac.0 * (view.x_max - view.x_min),
+            view.y_min + (1.0 - frac.1) * (view.y_max - view.y_min),
+        )
+    }
+
+    fn recenter_on(&mut self, frac: (f64, f64)) {
+        let (x, y) = self.point_at(frac);
+        self.center_x = x;
+        self.center_y = y;
     }
-    max_iter
-}
 
-fn iteration_to_color(iterations: u32, max_iterations: u32) -> Color {
-    if iterations == max_iterations {
-        Color::Black
-    } else {
-        let ratio = iterations as f64 / max_iterations as f64;
-        match (ratio * 8.0) as u32 {
-            0 => Color::Blue,
-            1 => Color::LightBlue,
-            2 => Color::Cyan,
-            3 => Color::Green,
-            4 => Color::Yellow,
-            5 => Color::LightRed,
-            6 => Color::Red,
-            7 => Color::Magenta,
-            _ => Color::White,
+    /// Zooms in or out while keeping the complex point under `frac` fixed on
+    /// screen, i.e. the standard "zoom toward cursor" transform.
+    fn zoom_toward(&mut self, frac: (f64, f64), zoom_in: bool) {
+        let cursor = self.point_at(frac);
+        if zoom_in {
+            self.zoom_in();
+        } else {
+            self.zoom_out();
         }
+        let aspect_ratio = CANVAS_WIDTH as f64 / CANVAS_HEIGHT as f64;
+        let range = 2.0 / self.zoom;
+        self.center_x = cursor.0 - (2.0 * frac.0 - 1.0) * aspect_ratio * range;
+        self.center_y = cursor.1 - (1.0 - 2.0 * frac.1) * range;
     }
 }
 
+/// Splits the terminal into the canvas area and the Info panel below it.
+/// Both `ui` and the mouse hit-testing in `run_app` split from this single
+/// definition so they can never drift apart.
+fn layout_chunks(size: Rect) -> std::rc::Rc<[Rect]> {
+    Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Min(0), Constraint::Length(6)].as_ref())
+        .split(size)
+}
+
+/// The screen area the canvas occupies.
+fn canvas_area(size: Rect) -> Rect {
+    layout_chunks(size)[0]
+}
+
+/// Converts an absolute terminal cell into a fractional position within
+/// `inner` (the canvas area minus its border), or `None` if outside it.
+fn mouse_to_view_fraction(inner: Rect, column: u16, row: u16) -> Option<(f64, f64)> {
+    if inner.width == 0 || inner.height == 0 {
+        return None;
+    }
+    if column < inner.x
+        || column >= inner.x + inner.width
+        || row < inner.y
+        || row >= inner.y + inner.height
+    {
+        return None;
+    }
+    let fx = (column - inner.x) as f64 / inner.width as f64;
+    let fy = (row - inner.y) as f64 / inner.height as f64;
+    Some((fx, fy))
+}
+
+/// Restores the terminal before handing off to the default panic hook, so a
+/// panic mid-render doesn't leave the shell in raw mode on the alternate
+/// screen with the mouse captured and the cursor hidden.
+fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |panic_info| {
+        let _ = disable_raw_mode();
+        let _ = execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture, Show);
+        default_hook(panic_info);
+    }));
+}
+
 fn main() -> Result<()> {
+    install_panic_hook();
+
     // setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -129,11 +307,18 @@ fn main() -> Result<()> {
 
 fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<()> {
     loop {
+        app.poll_export();
+
+        let frame_start = Instant::now();
+        let view = app.view_rect();
+        let recomputed_pixels = app.renderer.render(view, app.max_iterations);
+
         terminal.draw(|f| ui(f, &app))?;
+        app.frame_stats.record(frame_start.elapsed(), recomputed_pixels);
 
         if event::poll(Duration::from_millis(50))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
+            match event::read()? {
+                Event::Key(key) => match key.code {
                     KeyCode::Char('q') => return Ok(()),
                     KeyCode::Char('+') | KeyCode::Char('=') => app.zoom_in(),
                     KeyCode::Char('-') => app.zoom_out(),
@@ -143,52 +328,51 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<(
                     KeyCode::Down | KeyCode::Char('j') => app.move_down(),
                     KeyCode::Char('i') => app.increase_iterations(),
                     KeyCode::Char('d') => app.decrease_iterations(),
+                    KeyCode::Char('p') => app.cycle_palette(),
+                    KeyCode::Char('s') => app.export_view(),
                     _ => {}
+                },
+                Event::Mouse(mouse) => {
+                    let inner = Block::default()
+                        .borders(Borders::ALL)
+                        .inner(canvas_area(terminal.get_frame().area()));
+                    if let Some(frac) = mouse_to_view_fraction(inner, mouse.column, mouse.row) {
+                        match mouse.kind {
+                            MouseEventKind::Down(MouseButton::Left) => app.recenter_on(frac),
+                            MouseEventKind::ScrollUp => app.zoom_toward(frac, true),
+                            MouseEventKind::ScrollDown => app.zoom_toward(frac, false),
+                            _ => {}
+                        }
+                    }
                 }
+                _ => {}
             }
         }
     }
 }
 
 fn ui(f: &mut Frame, app: &App) {
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .margin(1)
-        .constraints([Constraint::Min(0), Constraint::Length(3)].as_ref())
-        .split(f.area());
+    let chunks = layout_chunks(f.area());
+
+    let buffer = app.renderer.buffer();
 
     let canvas = Canvas::default()
         .block(Block::default().borders(Borders::ALL).title("Mandelbrot Set"))
         .paint(|ctx| {
-            let width = 80.0; 
-            let height = 40.0; 
-            
-            
-            let aspect_ratio = width / height;
-            let range = 2.0 / app.zoom;
-            let x_min = app.center_x - range * aspect_ratio;
-            let x_max = app.center_x + range * aspect_ratio;
-            let y_min = app.center_y - range;
-            let y_max = app.center_y + range;
-
-            for i in 0..80 {
-                for j in 0..40 {
-                    let x = x_min + (i as f64 / width) * (x_max - x_min);
-                    let y = y_min + (j as f64 / height) * (y_max - y_min);
-                    
-                    let iterations = mandelbrot_iterations((x, y), app.max_iterations);
-                    let color = iteration_to_color(iterations, app.max_iterations);
-                    
+            for (j, row) in buffer.iter().enumerate() {
+                for (i, &sample) in row.iter().enumerate() {
+                    let color = palette_color(app.palette, sample, app.max_iterations);
+
                     ctx.print(
                         i as f64,
                         j as f64,
-                        Span::styled("â–ˆ", Style::default().fg(color))
+                        Span::styled("█", Style::default().fg(color)),
                     );
                 }
             }
         })
-        .x_bounds([0.0, 80.0])
-        .y_bounds([0.0, 40.0]);
+        .x_bounds([0.0, CANVAS_WIDTH as f64])
+        .y_bounds([0.0, CANVAS_HEIGHT as f64]);
 
     f.render_widget(canvas, chunks[0]);
 
@@ -202,12 +386,29 @@ fn ui(f: &mut Frame, app: &App) {
             Span::styled("arrows/hjkl", Style::default().fg(Color::Yellow)),
             Span::raw(" move | "),
             Span::styled("i/d", Style::default().fg(Color::Yellow)),
-            Span::raw(" iterations"),
+            Span::raw(" iterations | "),
+            Span::styled("click/scroll", Style::default().fg(Color::Yellow)),
+            Span::raw(" pan/zoom | "),
+            Span::styled("p", Style::default().fg(Color::Yellow)),
+            Span::raw(" palette | "),
+            Span::styled("s", Style::default().fg(Color::Yellow)),
+            Span::raw(" save image"),
+        ]),
+        Line::from(vec![
+            Span::raw(format!("Zoom: {:.2} | Center: ({:.4}, {:.4}) | Iterations: {} | Palette: {}",
+                app.zoom, app.center_x, app.center_y, app.max_iterations, app.palette.name())),
         ]),
         Line::from(vec![
-            Span::raw(format!("Zoom: {:.2} | Center: ({:.4}, {:.4}) | Iterations: {}", 
-                app.zoom, app.center_x, app.center_y, app.max_iterations)),
+            Span::raw(format!(
+                "{:.1} ms/frame | {:.1} FPS | {} px recomputed",
+                app.frame_stats.avg_ms(),
+                app.frame_stats.fps(),
+                app.frame_stats.recomputed_pixels,
+            )),
         ]),
+        Line::from(vec![Span::raw(
+            app.last_export.clone().unwrap_or_default(),
+        )]),
     ])
     .block(Block::default().borders(Borders::ALL).title("Info"));
 
@@ -219,16 +420,58 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_mandelbrot_iterations() {
-        assert_eq!(mandelbrot_iterations((0.0, 0.0), 100), 100);
-        
-        assert!(mandelbrot_iterations((2.0, 2.0), 100) < 100);
+    fn mouse_to_view_fraction_only_inside_inner_rect() {
+        let inner = Rect {
+            x: 2,
+            y: 1,
+            width: 10,
+            height: 5,
+        };
+        assert_eq!(mouse_to_view_fraction(inner, 2, 1), Some((0.0, 0.0)));
+        assert!(mouse_to_view_fraction(inner, 1, 1).is_none());
+        assert!(mouse_to_view_fraction(inner, 12, 1).is_none());
+    }
+
+    #[test]
+    fn zoom_toward_keeps_the_cursor_point_fixed() {
+        let mut app = App::default();
+        let frac = (0.75, 0.25);
+        let before = app.point_at(frac);
+
+        app.zoom_toward(frac, true);
+
+        let after = app.point_at(frac);
+        assert!((before.0 - after.0).abs() < 1e-9);
+        assert!((before.1 - after.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn frame_stats_starts_empty() {
+        let stats = FrameStats::new();
+        assert_eq!(stats.avg_ms(), 0.0);
+        assert_eq!(stats.fps(), 0.0);
     }
 
     #[test]
-    fn test_color_mapping() {
-        assert_eq!(iteration_to_color(100, 100), Color::Black);
-        
-        assert_eq!(iteration_to_color(0, 100), Color::Blue);
+    fn frame_stats_averages_and_converts_to_fps() {
+        let mut stats = FrameStats::new();
+        stats.record(Duration::from_millis(10), 100);
+        stats.record(Duration::from_millis(20), 200);
+
+        assert!((stats.avg_ms() - 15.0).abs() < 1e-9);
+        assert!((stats.fps() - 1000.0 / 15.0).abs() < 1e-9);
+        assert_eq!(stats.recomputed_pixels, 200);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn frame_stats_drops_oldest_sample_past_the_window() {
+        let mut stats = FrameStats::new();
+        for _ in 0..FrameStats::WINDOW {
+            stats.record(Duration::from_millis(10), 0);
+        }
+        stats.record(Duration::from_millis(100), 0);
+
+        assert_eq!(stats.history.len(), FrameStats::WINDOW);
+        assert!(stats.avg_ms() > 10.0);
+    }
+}
@@ -0,0 +1,152 @@
+//! Core Mandelbrot escape-time math, smooth coloring, and palettes.
+
+use ratatui::style::Color;
+
+/// Runs the escape-time iteration for `c` up to `max_iter` steps. Returns the
+/// iteration count at which `|z|` exceeded the bailout radius (or `max_iter`
+/// if `c` appears to be in the set) along with the final `z`. A couple of
+/// extra steps are taken past the bailout so that final `z` gives a cleaner
+/// logarithm for smooth coloring.
+pub fn mandelbrot_iterations(c: (f64, f64), max_iter: u32) -> (u32, (f64, f64)) {
+    let mut z = (0.0, 0.0);
+    for i in 0..max_iter {
+        if z.0 * z.0 + z.1 * z.1 > 4.0 {
+            for _ in 0..2 {
+                z = (z.0 * z.0 - z.1 * z.1 + c.0, 2.0 * z.0 * z.1 + c.1);
+            }
+            return (i, z);
+        }
+        z = (z.0 * z.0 - z.1 * z.1 + c.0, 2.0 * z.0 * z.1 + c.1);
+    }
+    (max_iter, z)
+}
+
+/// Converts a raw iteration count and the final `z` it escaped at into a
+/// fractional ("smooth") iteration count, removing the banding that comes
+/// from coloring by the raw integer count alone.
+pub fn smooth_iteration_count(n: u32, z: (f64, f64), max_iterations: u32) -> f64 {
+    if n >= max_iterations {
+        return max_iterations as f64;
+    }
+    let magnitude = (z.0 * z.0 + z.1 * z.1).sqrt();
+    n as f64 + 1.0 - (magnitude.ln()).ln() / std::f64::consts::LN_2
+}
+
+/// A point's full escape-time sample: its smooth iteration count and whether
+/// it escaped at all (points that never escape are considered inside the set).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EscapeSample {
+    pub mu: f64,
+    pub escaped: bool,
+}
+
+pub fn sample(c: (f64, f64), max_iterations: u32) -> EscapeSample {
+    let (n, z) = mandelbrot_iterations(c, max_iterations);
+    EscapeSample {
+        mu: smooth_iteration_count(n, z, max_iterations),
+        escaped: n < max_iterations,
+    }
+}
+
+/// The available continuous color palettes, cycled with a key binding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Palette {
+    Rainbow,
+    Grayscale,
+    Fire,
+}
+
+impl Palette {
+    pub fn next(self) -> Palette {
+        match self {
+            Palette::Rainbow => Palette::Grayscale,
+            Palette::Grayscale => Palette::Fire,
+            Palette::Fire => Palette::Rainbow,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Palette::Rainbow => "rainbow",
+            Palette::Grayscale => "grayscale",
+            Palette::Fire => "fire",
+        }
+    }
+}
+
+/// Maps an escape sample through `palette` to a 24-bit terminal color.
+pub fn palette_color(palette: Palette, sample: EscapeSample, max_iterations: u32) -> Color {
+    if !sample.escaped {
+        return Color::Black;
+    }
+    let t = (sample.mu / max_iterations as f64).clamp(0.0, 1.0);
+    match palette {
+        Palette::Rainbow => rainbow_color(t),
+        Palette::Grayscale => grayscale_color(t),
+        Palette::Fire => fire_color(t),
+    }
+}
+
+/// An extended rainbow cycle: three offset cosine waves sweeping through hue
+/// space several times over `t` so deep zooms still show rich gradients
+/// rather than a single muddy band.
+fn rainbow_color(t: f64) -> Color {
+    const TAU: f64 = std::f64::consts::TAU;
+    const CYCLES: f64 = 5.0;
+    let r = 0.5 + 0.5 * (TAU * (t * CYCLES)).cos();
+    let g = 0.5 + 0.5 * (TAU * (t * CYCLES + 1.0 / 3.0)).cos();
+    let b = 0.5 + 0.5 * (TAU * (t * CYCLES + 2.0 / 3.0)).cos();
+    Color::Rgb((r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8)
+}
+
+/// Converts a terminal `Color` produced by [`palette_color`] into raw RGB
+/// bytes, for callers (like the PPM exporter) that need pixel bytes rather
+/// than a `ratatui` style.
+pub fn color_to_rgb(color: Color) -> (u8, u8, u8) {
+    match color {
+        Color::Rgb(r, g, b) => (r, g, b),
+        _ => (0, 0, 0),
+    }
+}
+
+fn grayscale_color(t: f64) -> Color {
+    let v = (t * 255.0) as u8;
+    Color::Rgb(v, v, v)
+}
+
+/// Black through red and orange to a pale yellow-white, like a fire gradient.
+fn fire_color(t: f64) -> Color {
+    let r = (t * 3.0).clamp(0.0, 1.0);
+    let g = ((t - 1.0 / 3.0) * 3.0).clamp(0.0, 1.0);
+    let b = ((t - 2.0 / 3.0) * 3.0).clamp(0.0, 1.0);
+    Color::Rgb((r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mandelbrot_iterations() {
+        let (n, _) = mandelbrot_iterations((0.0, 0.0), 100);
+        assert_eq!(n, 100);
+
+        let (n, _) = mandelbrot_iterations((2.0, 2.0), 100);
+        assert!(n < 100);
+    }
+
+    #[test]
+    fn test_smooth_iteration_count_saturates_inside_set() {
+        let (n, z) = mandelbrot_iterations((0.0, 0.0), 100);
+        assert_eq!(smooth_iteration_count(n, z, 100), 100.0);
+    }
+
+    #[test]
+    fn test_palette_color_inside_set_is_black() {
+        let inside = EscapeSample {
+            mu: 100.0,
+            escaped: false,
+        };
+        assert_eq!(palette_color(Palette::Rainbow, inside, 100), Color::Black);
+    }
+}
@@ -0,0 +1,200 @@
+//! Parallel, chunked Mandelbrot renderer with double-buffered output.
+//!
+//! The view is split into small horizontal strips ("chunks") that are
+//! farmed out to a fixed pool of worker threads sized to
+//! [`std::thread::available_parallelism`]. Workers send back their rows of
+//! escape samples, which are written into an off-screen buffer; only once every chunk for the current
+//! frame has landed is that buffer swapped in as the new visible one, so a
+//! caller never observes a partially-computed frame.
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::mandelbrot::{sample, EscapeSample};
+
+/// Number of canvas rows handed to a worker in a single job.
+const ROWS_PER_CHUNK: usize = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ViewRect {
+    pub x_min: f64,
+    pub x_max: f64,
+    pub y_min: f64,
+    pub y_max: f64,
+}
+
+struct MandelChunk {
+    view_rect: ViewRect,
+    y_min: usize,
+    y_max: usize,
+    width: usize,
+    height: usize,
+    max_iterations: u32,
+}
+
+struct ChunkResult {
+    y_min: usize,
+    rows: Vec<Vec<EscapeSample>>,
+}
+
+/// A fixed pool of worker threads that compute Mandelbrot chunks on demand
+/// and a pair of buffers (`pixels`, `npixels`) swapped once a frame completes.
+pub struct ChunkRenderer {
+    job_tx: Sender<MandelChunk>,
+    result_rx: Receiver<ChunkResult>,
+    width: usize,
+    height: usize,
+    pixels: Vec<Vec<EscapeSample>>,
+    npixels: Vec<Vec<EscapeSample>>,
+    last_view: Option<(ViewRect, u32)>,
+}
+
+impl ChunkRenderer {
+    pub fn new(width: usize, height: usize) -> Self {
+        let worker_count = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        let (job_tx, job_rx) = mpsc::channel::<MandelChunk>();
+        let job_rx = Arc::new(Mutex::new(job_rx));
+        let (result_tx, result_rx) = mpsc::channel::<ChunkResult>();
+
+        for _ in 0..worker_count {
+            let job_rx = Arc::clone(&job_rx);
+            let result_tx = result_tx.clone();
+            thread::spawn(move || loop {
+                let job = {
+                    let rx = job_rx.lock().unwrap();
+                    rx.recv()
+                };
+                let job = match job {
+                    Ok(job) => job,
+                    Err(_) => break,
+                };
+
+                let rows = (job.y_min..job.y_max)
+                    .map(|j| {
+                        (0..job.width)
+                            .map(|i| {
+                                let x = job.view_rect.x_min
+                                    + (i as f64 / job.width as f64)
+                                        * (job.view_rect.x_max - job.view_rect.x_min);
+                                let y = job.view_rect.y_min
+                                    + (j as f64 / job.height as f64)
+                                        * (job.view_rect.y_max - job.view_rect.y_min);
+                                sample((x, y), job.max_iterations)
+                            })
+                            .collect()
+                    })
+                    .collect();
+
+                if result_tx
+                    .send(ChunkResult {
+                        y_min: job.y_min,
+                        rows,
+                    })
+                    .is_err()
+                {
+                    break;
+                }
+            });
+        }
+
+        ChunkRenderer {
+            job_tx,
+            result_rx,
+            width,
+            height,
+            pixels: vec![vec![EscapeSample::default(); width]; height],
+            npixels: vec![vec![EscapeSample::default(); width]; height],
+            last_view: None,
+        }
+    }
+
+    /// Recomputes the view if it differs from the last rendered one.
+    /// Returns the number of pixels recomputed this call (0 if the view and
+    /// iteration count were unchanged, in which case the existing buffer is
+    /// left untouched).
+    pub fn render(&mut self, view: ViewRect, max_iterations: u32) -> usize {
+        if self.last_view == Some((view, max_iterations)) {
+            return 0;
+        }
+
+        let mut y = 0;
+        let mut chunk_count = 0;
+        while y < self.height {
+            let y_max = (y + ROWS_PER_CHUNK).min(self.height);
+            self.job_tx
+                .send(MandelChunk {
+                    view_rect: view,
+                    y_min: y,
+                    y_max,
+                    width: self.width,
+                    height: self.height,
+                    max_iterations,
+                })
+                .expect("renderer worker pool disconnected");
+            chunk_count += 1;
+            y = y_max;
+        }
+
+        for _ in 0..chunk_count {
+            let result = self
+                .result_rx
+                .recv()
+                .expect("renderer worker pool disconnected");
+            for (offset, row) in result.rows.into_iter().enumerate() {
+                self.npixels[result.y_min + offset] = row;
+            }
+        }
+
+        std::mem::swap(&mut self.pixels, &mut self.npixels);
+        self.last_view = Some((view, max_iterations));
+        self.width * self.height
+    }
+
+    pub fn buffer(&self) -> &[Vec<EscapeSample>] {
+        &self.pixels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VIEW: ViewRect = ViewRect {
+        x_min: -1.0,
+        x_max: 1.0,
+        y_min: -1.0,
+        y_max: 1.0,
+    };
+
+    #[test]
+    fn render_recomputes_only_when_view_changes() {
+        let mut renderer = ChunkRenderer::new(4, 4);
+        assert_eq!(renderer.render(VIEW, 20), 16);
+        assert_eq!(renderer.render(VIEW, 20), 0);
+
+        let other_view = ViewRect {
+            x_max: 2.0,
+            ..VIEW
+        };
+        assert_eq!(renderer.render(other_view, 20), 16);
+    }
+
+    #[test]
+    fn render_matches_direct_sampling() {
+        let (width, height) = (4, 4);
+        let mut renderer = ChunkRenderer::new(width, height);
+        renderer.render(VIEW, 20);
+        let buffer = renderer.buffer();
+
+        for (j, row) in buffer.iter().enumerate() {
+            for (i, pixel) in row.iter().enumerate() {
+                let x = VIEW.x_min + (i as f64 / width as f64) * (VIEW.x_max - VIEW.x_min);
+                let y = VIEW.y_min + (j as f64 / height as f64) * (VIEW.y_max - VIEW.y_min);
+                let expected = sample((x, y), 20);
+                assert_eq!(pixel.escaped, expected.escaped);
+                assert!((pixel.mu - expected.mu).abs() < 1e-9);
+            }
+        }
+    }
+}